@@ -0,0 +1,113 @@
+use keyring::Entry;
+use rand::RngCore;
+use rusqlite::Connection;
+use std::{fs, io::Read, path::Path};
+
+use crate::migrations;
+
+const KEYRING_SERVICE: &str = "financex";
+const KEYRING_USER: &str = "db-encryption-key";
+
+/// The header every plaintext SQLite file starts with. An encrypted
+/// SQLCipher file's first page is ciphertext, so it never matches this.
+const SQLITE_PLAINTEXT_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Reads the SQLCipher key from the OS keychain, generating and storing a
+/// fresh random one whenever the keychain has no entry for it. Whether an
+/// encrypted `financex.db` already exists is not checked, so a lost entry is
+/// replaced by a new key rather than reported as an error. A finance app's
+/// database must never sit on disk as plaintext, so this key is required
+/// before we ever open `financex.db`.
+pub fn load_or_create_key() -> Result<String, String> {
+    let entry = Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|err| err.to_string())?;
+    match entry.get_password() {
+        Ok(key) => Ok(key),
+        Err(keyring::Error::NoEntry) => {
+            let key = generate_key();
+            entry.set_password(&key).map_err(|err| err.to_string())?;
+            Ok(key)
+        }
+        Err(err) => Err(format!(
+            "Veritabanı şifreleme anahtarı okunamadı: {}. Anahtar kasa (keyring) girişine \
+             erişilemiyor; verileriniz bozulmamış ama kilitli.",
+            err
+        )),
+    }
+}
+
+fn generate_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Whether `path` is a plaintext (unencrypted) SQLite file, judged by its
+/// own header rather than by what the linked library happens to support
+/// (`PRAGMA cipher_version` answers "is this build SQLCipher?", not
+/// "is this file encrypted?", and is true/false for every file on a given
+/// build regardless of its actual contents).
+fn is_plaintext_sqlite(path: &Path) -> Result<bool, String> {
+    let mut file = fs::File::open(path).map_err(|err| err.to_string())?;
+    let mut header = [0u8; 16];
+    let read = file.read(&mut header).map_err(|err| err.to_string())?;
+    Ok(read == 16 && &header == SQLITE_PLAINTEXT_HEADER)
+}
+
+/// Fails loudly if `PRAGMA key` didn't actually turn on encryption — e.g. a
+/// `rusqlite`/`libsqlite3-sys` build without the `sqlcipher` feature silently
+/// ignores the pragma, which would otherwise leave a finance app's data
+/// written to disk as plaintext while believing it's encrypted.
+pub fn assert_encryption_active(conn: &Connection) -> Result<(), String> {
+    let cipher_version: String = conn
+        .pragma_query_value(None, "cipher_version", |row| row.get(0))
+        .unwrap_or_default();
+    if cipher_version.is_empty() {
+        return Err(
+            "SQLCipher etkin değil: bu derleme şifreleme desteği olmadan yapılmış. \
+             Finans verileri düz metin olarak yazılmayacak; lütfen sqlcipher özellikli \
+             bir derleme kullanın."
+                .to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Re-encrypts a pre-existing plaintext `financex.db` in place via
+/// SQLCipher's `sqlcipher_export`, so users upgrading from an older release
+/// don't lose their data. A no-op once the file is already encrypted, and
+/// guarded by the on-disk schema version so a file from a newer binary is
+/// never touched.
+pub fn migrate_plaintext_to_encrypted(path: &Path, key: &str) -> Result<(), String> {
+    if !path.exists() || !is_plaintext_sqlite(path)? {
+        return Ok(());
+    }
+
+    let plain = Connection::open(path).map_err(|err| err.to_string())?;
+    let on_disk_version: u32 = plain
+        .query_row("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })
+        .unwrap_or(0);
+    if on_disk_version > migrations::CURRENT_DB_VERSION {
+        return Err(format!(
+            "Veritabanı sürümü ({}) bu uygulamanın desteklediği sürümden ({}) daha yeni; \
+             şifreleme geçişi güvenlik nedeniyle atlandı.",
+            on_disk_version,
+            migrations::CURRENT_DB_VERSION
+        ));
+    }
+
+    let staging_path = path.with_extension("db.encrypting");
+    plain
+        .execute_batch(&format!(
+            "ATTACH DATABASE '{}' AS encrypted KEY '{}';
+             SELECT sqlcipher_export('encrypted');
+             DETACH DATABASE encrypted;",
+            staging_path.display(),
+            key
+        ))
+        .map_err(|err| err.to_string())?;
+    drop(plain);
+
+    fs::rename(&staging_path, path).map_err(|err| err.to_string())
+}
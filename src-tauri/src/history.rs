@@ -0,0 +1,205 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+
+use crate::{current_state_value, write_state_value, AppDatabase};
+
+/// How many past `app_state` values to keep before the oldest are dropped.
+const MAX_HISTORY_REVISIONS: i64 = 100;
+
+#[derive(Debug, Serialize)]
+pub struct RevisionInfo {
+    pub id: i64,
+    pub revision: i64,
+    pub created_at: String,
+}
+
+/// Archives `previous_value` as a new history revision and clears any
+/// pending redo, since a fresh edit invalidates it. Called by `save_state`
+/// before it overwrites the live value.
+pub fn record_previous_value(conn: &Connection, previous_value: &str) -> rusqlite::Result<()> {
+    let next_revision: i64 =
+        conn.query_row("SELECT COALESCE(MAX(revision), 0) + 1 FROM app_state_history", [], |row| {
+            row.get(0)
+        })?;
+    conn.execute(
+        "INSERT INTO app_state_history (revision, value) VALUES (?1, ?2)",
+        params![next_revision, previous_value],
+    )?;
+    conn.execute(
+        "DELETE FROM app_state_history WHERE revision <= (SELECT MAX(revision) - ?1 FROM app_state_history)",
+        params![MAX_HISTORY_REVISIONS],
+    )?;
+    conn.execute(
+        "UPDATE app_state_cursor SET position = 0, stashed_tip = NULL WHERE id = 1",
+        [],
+    )?;
+    Ok(())
+}
+
+fn cursor_position(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.query_row("SELECT position FROM app_state_cursor WHERE id = 1", [], |row| row.get(0))
+}
+
+fn revision_at_depth(conn: &Connection, depth: i64) -> rusqlite::Result<String> {
+    conn.query_row(
+        "SELECT value FROM app_state_history ORDER BY revision DESC LIMIT 1 OFFSET ?1",
+        params![depth - 1],
+        |row| row.get(0),
+    )
+}
+
+#[tauri::command]
+pub fn list_revisions(database: State<AppDatabase>) -> Result<Vec<RevisionInfo>, String> {
+    database.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, revision, created_at FROM app_state_history ORDER BY revision DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RevisionInfo {
+                id: row.get(0)?,
+                revision: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    })
+}
+
+/// Read-modify-write across `app_state_history`/`kv_store`; the pool can
+/// hand a concurrent command its own connection, so this has to be atomic
+/// rather than relying on serialized access like the old Mutex did.
+fn restore_revision_impl(conn: &Connection, id: i64) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let value: String = tx.query_row(
+        "SELECT value FROM app_state_history WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    if let Some(previous) = current_state_value(&tx)? {
+        record_previous_value(&tx, &previous)?;
+    }
+    write_state_value(&tx, &value)?;
+    tx.commit()
+}
+
+fn undo_impl(conn: &Connection) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let position = cursor_position(&tx)?;
+    let history_count: i64 = tx.query_row("SELECT COUNT(*) FROM app_state_history", [], |row| row.get(0))?;
+    if position >= history_count {
+        return tx.commit();
+    }
+
+    if position == 0 {
+        let tip = current_state_value(&tx)?;
+        tx.execute(
+            "UPDATE app_state_cursor SET stashed_tip = ?1 WHERE id = 1",
+            params![tip],
+        )?;
+    }
+
+    let new_position = position + 1;
+    let target_value = revision_at_depth(&tx, new_position)?;
+    write_state_value(&tx, &target_value)?;
+    tx.execute(
+        "UPDATE app_state_cursor SET position = ?1 WHERE id = 1",
+        params![new_position],
+    )?;
+    tx.commit()
+}
+
+fn redo_impl(conn: &Connection) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    let position = cursor_position(&tx)?;
+    if position == 0 {
+        return tx.commit();
+    }
+
+    let new_position = position - 1;
+    if new_position == 0 {
+        let stashed: Option<String> = tx
+            .query_row("SELECT stashed_tip FROM app_state_cursor WHERE id = 1", [], |row| row.get(0))
+            .optional()?
+            .flatten();
+        if let Some(value) = stashed {
+            write_state_value(&tx, &value)?;
+        }
+        tx.execute(
+            "UPDATE app_state_cursor SET position = 0, stashed_tip = NULL WHERE id = 1",
+            [],
+        )?;
+    } else {
+        let target_value = revision_at_depth(&tx, new_position)?;
+        write_state_value(&tx, &target_value)?;
+        tx.execute(
+            "UPDATE app_state_cursor SET position = ?1 WHERE id = 1",
+            params![new_position],
+        )?;
+    }
+    tx.commit()
+}
+
+#[tauri::command]
+pub fn restore_revision(id: i64, database: State<AppDatabase>) -> Result<(), String> {
+    database.with_conn(|conn| restore_revision_impl(conn, id))
+}
+
+#[tauri::command]
+pub fn undo(database: State<AppDatabase>) -> Result<(), String> {
+    database.with_conn(undo_impl)
+}
+
+#[tauri::command]
+pub fn redo(database: State<AppDatabase>) -> Result<(), String> {
+    database.with_conn(redo_impl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::save_state_impl;
+
+    fn test_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::migrations::run_migrations(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn save_undo_redo_restore_round_trip() {
+        let conn = test_conn();
+
+        save_state_impl(&conn, "v1").unwrap();
+        save_state_impl(&conn, "v2").unwrap();
+        save_state_impl(&conn, "v3").unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v3"));
+
+        undo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v2"));
+
+        undo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v1"));
+
+        // No earlier revision to undo to; state stays put.
+        undo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v1"));
+
+        redo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v2"));
+
+        redo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v3"));
+
+        // A fresh edit clears the redo stack.
+        save_state_impl(&conn, "v4").unwrap();
+        undo_impl(&conn).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v3"));
+
+        let first_revision_id: i64 = conn
+            .query_row("SELECT id FROM app_state_history ORDER BY revision ASC LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        restore_revision_impl(&conn, first_revision_id).unwrap();
+        assert_eq!(current_state_value(&conn).unwrap().as_deref(), Some("v1"));
+    }
+}
@@ -0,0 +1,70 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+
+use crate::AppDatabase;
+
+pub(crate) const TABLE_KV: &str = "kv_store";
+
+/// Reads a raw BLOB value by key. `None` if the key is absent.
+pub(crate) fn get_value(conn: &Connection, key: &str) -> rusqlite::Result<Option<Vec<u8>>> {
+    conn.query_row(
+        &format!("SELECT value FROM {} WHERE key = ?1", TABLE_KV),
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Writes a raw BLOB value, overwriting any existing value under `key`.
+pub(crate) fn set_value(conn: &Connection, key: &str, value: &[u8]) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value=excluded.value",
+            TABLE_KV
+        ),
+        params![key, value],
+    )
+    .map(|_| ())
+}
+
+pub(crate) fn delete_value(conn: &Connection, key: &str) -> rusqlite::Result<()> {
+    conn.execute(&format!("DELETE FROM {} WHERE key = ?1", TABLE_KV), params![key])
+        .map(|_| ())
+}
+
+/// Lists every key starting with `prefix`, so callers can namespace their
+/// own partitions (e.g. `"settings:"`, `"profile:3:"`, `"fx-rate-cache:"`)
+/// without the store needing to know about them.
+///
+/// A lexicographic range scan rather than `LIKE ?1 || '%'`, so a prefix
+/// containing `%` or `_` is matched literally instead of as a wildcard.
+pub(crate) fn list_keys(conn: &Connection, prefix: &str) -> rusqlite::Result<Vec<String>> {
+    let upper_bound = format!("{}\u{10ffff}", prefix);
+    let mut stmt = conn.prepare(&format!(
+        "SELECT key FROM {} WHERE key >= ?1 AND key < ?2 ORDER BY key",
+        TABLE_KV
+    ))?;
+    let rows = stmt.query_map(params![prefix, upper_bound], |row| row.get(0))?;
+    rows.collect()
+}
+
+#[tauri::command]
+pub fn kv_get(key: String, database: State<AppDatabase>) -> Result<Option<Vec<u8>>, String> {
+    database.with_conn(|conn| get_value(conn, &key))
+}
+
+#[tauri::command]
+pub fn kv_set(key: String, value: Vec<u8>, database: State<AppDatabase>) -> Result<(), String> {
+    database.with_conn(|conn| set_value(conn, &key, &value))
+}
+
+#[tauri::command]
+pub fn kv_delete(key: String, database: State<AppDatabase>) -> Result<(), String> {
+    database.with_conn(|conn| delete_value(conn, &key))
+}
+
+#[tauri::command]
+pub fn kv_list_keys(prefix: String, database: State<AppDatabase>) -> Result<Vec<String>, String> {
+    database.with_conn(|conn| list_keys(conn, &prefix))
+}
@@ -1,57 +1,79 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use rusqlite::{params, Connection, OptionalExtension};
-use std::{fs, sync::Mutex};
+mod crypto;
+mod history;
+mod kv;
+mod migrations;
+mod transactions;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::Connection;
+use std::fs;
 use tauri::{Manager, State};
 
 const DB_FILE_NAME: &str = "financex.db";
-const TABLE_KV: &str = "kv_store";
-const APP_STATE_KEY: &str = "app_state";
+pub(crate) const APP_STATE_KEY: &str = "app_state";
+/// A long report query shouldn't stall a write, so we hand out pooled
+/// connections instead of serializing every command behind one `Mutex`.
+const BUSY_TIMEOUT_MS: u32 = 5_000;
 
-struct AppDatabase(Mutex<Connection>);
+pub(crate) struct AppDatabase(Pool<SqliteConnectionManager>);
 
 impl AppDatabase {
-    fn new(connection: Connection) -> Self {
-        Self(Mutex::new(connection))
+    fn new(pool: Pool<SqliteConnectionManager>) -> Self {
+        Self(pool)
     }
 
-    fn with_conn<R, F>(&self, action: F) -> Result<R, String>
+    pub(crate) fn with_conn<R, F>(&self, action: F) -> Result<R, String>
     where
         F: FnOnce(&Connection) -> Result<R, rusqlite::Error>,
     {
-        let conn = self.0.lock().map_err(|_| "Veritabanı kilidi alınamadı".to_string())?;
+        let conn = self.0.get().map_err(|err| err.to_string())?;
         action(&conn).map_err(|err| err.to_string())
     }
 }
 
+/// Reads the current `app_state` value directly, bypassing the
+/// history/undo machinery. Shared by `load_state` and by [`history`],
+/// which needs the pre-write value to archive it.
+pub(crate) fn current_state_value(conn: &Connection) -> rusqlite::Result<Option<String>> {
+    let bytes = kv::get_value(conn, APP_STATE_KEY)?;
+    Ok(bytes.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// Overwrites the current `app_state` value directly, bypassing the
+/// history/undo machinery. Shared by `save_state` and by [`history`]'s
+/// undo/redo/restore commands, which decide for themselves when a write
+/// should be archived.
+pub(crate) fn write_state_value(conn: &Connection, value: &str) -> rusqlite::Result<()> {
+    kv::set_value(conn, APP_STATE_KEY, value.as_bytes())
+}
+
 #[tauri::command]
 fn load_state(database: State<AppDatabase>) -> Result<Option<String>, String> {
-    database.with_conn(|conn| {
-        conn.query_row(
-            &format!("SELECT value FROM {} WHERE key = ?1", TABLE_KV),
-            params![APP_STATE_KEY],
-            |row| row.get::<_, String>(0),
-        )
-        .optional()
-    })
+    database.with_conn(current_state_value)
+}
+
+/// Archives the current value (if any) to history, then overwrites it.
+/// The pool hands out one connection per concurrent command, so without a
+/// transaction two overlapping saves could each read the same "previous"
+/// value and race on `record_previous_value`'s revision numbering.
+pub(crate) fn save_state_impl(conn: &Connection, state: &str) -> rusqlite::Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    if let Some(previous) = current_state_value(&tx)? {
+        history::record_previous_value(&tx, &previous)?;
+    }
+    write_state_value(&tx, state)?;
+    tx.commit()
 }
 
 #[tauri::command]
 fn save_state(state: String, database: State<AppDatabase>) -> Result<(), String> {
-    database.with_conn(|conn| {
-        conn.execute(
-            &format!(
-                "INSERT INTO {} (key, value) VALUES (?1, ?2)
-                 ON CONFLICT(key) DO UPDATE SET value=excluded.value",
-                TABLE_KV
-            ),
-            params![APP_STATE_KEY, state],
-        )
-        .map(|_| ())
-    })
+    database.with_conn(|conn| save_state_impl(conn, &state))
 }
 
-fn initialise_database(app: &tauri::AppHandle) -> Result<Connection, String> {
+fn initialise_database(app: &tauri::AppHandle) -> Result<Pool<SqliteConnectionManager>, String> {
     let app_dir = app
         .path_resolver()
         .app_data_dir()
@@ -59,32 +81,49 @@ fn initialise_database(app: &tauri::AppHandle) -> Result<Connection, String> {
     fs::create_dir_all(&app_dir).map_err(|err| err.to_string())?;
 
     let db_path = app_dir.join(DB_FILE_NAME);
-    let connection = Connection::open(db_path).map_err(|err| err.to_string())?;
-    connection
-        .execute(
-            &format!(
-                "CREATE TABLE IF NOT EXISTS {} (
-                    key TEXT PRIMARY KEY,
-                    value TEXT NOT NULL
-                )",
-                TABLE_KV
-            ),
-            [],
-        )
-        .map_err(|err| err.to_string())?;
-
-    Ok(connection)
+    let db_key = crypto::load_or_create_key()?;
+    crypto::migrate_plaintext_to_encrypted(&db_path, &db_key)?;
+
+    let manager = SqliteConnectionManager::file(db_path).with_init(move |conn| {
+        conn.execute_batch(&format!(
+            "PRAGMA key='{}'; PRAGMA journal_mode=WAL; PRAGMA busy_timeout={}; PRAGMA foreign_keys=ON;",
+            db_key, BUSY_TIMEOUT_MS
+        ))
+    });
+    let pool = Pool::new(manager).map_err(|err| err.to_string())?;
+
+    let connection = pool.get().map_err(|err| err.to_string())?;
+    crypto::assert_encryption_active(&connection)?;
+    migrations::run_migrations(&connection)?;
+
+    Ok(pool)
 }
 
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
             let app_handle = app.handle();
-            let connection = initialise_database(&app_handle)?;
-            app.manage(AppDatabase::new(connection));
+            let pool = initialise_database(&app_handle)?;
+            app.manage(AppDatabase::new(pool));
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![load_state, save_state])
+        .invoke_handler(tauri::generate_handler![
+            load_state,
+            save_state,
+            transactions::create_transaction,
+            transactions::list_transactions,
+            transactions::update_transaction,
+            transactions::delete_transaction,
+            transactions::sum_by_category,
+            history::list_revisions,
+            history::restore_revision,
+            history::undo,
+            history::redo,
+            kv::kv_get,
+            kv::kv_set,
+            kv::kv_delete,
+            kv::kv_list_keys,
+        ])
         .run(tauri::generate_context!())
         .expect("Tauri uygulaması çalıştırılırken hata oluştu");
 }
@@ -0,0 +1,145 @@
+use rusqlite::Connection;
+
+/// Highest schema version this binary knows how to run.
+pub const CURRENT_DB_VERSION: u32 = 4;
+
+/// One versioned upgrade step, named after the refinery `V{version}__{name}.sql`
+/// convention so the file list on disk stays self-documenting.
+struct Migration {
+    version: u32,
+    name: &'static str,
+    sql: &'static str,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("../migrations/V1__initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "accounts_transactions_categories",
+        sql: include_str!("../migrations/V2__accounts_transactions_categories.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "app_state_history",
+        sql: include_str!("../migrations/V3__app_state_history.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "kv_store_blob_values",
+        sql: include_str!("../migrations/V4__kv_store_blob_values.sql"),
+    },
+];
+
+/// Brings `conn` up to [`CURRENT_DB_VERSION`], applying every pending
+/// migration inside a single transaction and recording progress in
+/// `schema_migrations`. Fails loudly if the database was created by a
+/// newer binary than this one, instead of silently corrupting it.
+pub fn run_migrations(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        )",
+        [],
+    )
+    .map_err(|err| err.to_string())?;
+
+    let on_disk_version: u32 = conn
+        .query_row("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", [], |row| {
+            row.get(0)
+        })
+        .map_err(|err| err.to_string())?;
+
+    if on_disk_version > CURRENT_DB_VERSION {
+        return Err(format!(
+            "Veritabanı sürümü ({}) bu uygulamanın desteklediği sürümden ({}) daha yeni. \
+             Lütfen FinanceX'i güncelleyin.",
+            on_disk_version, CURRENT_DB_VERSION
+        ));
+    }
+
+    let pending = MIGRATIONS.iter().filter(|m| m.version > on_disk_version);
+
+    let tx = conn.unchecked_transaction().map_err(|err| err.to_string())?;
+    for migration in pending {
+        tx.execute_batch(migration.sql).map_err(|err| {
+            format!("Migration V{}__{} başarısız oldu: {}", migration.version, migration.name, err)
+        })?;
+        tx.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            rusqlite::params![migration.version],
+        )
+        .map_err(|err| err.to_string())?;
+    }
+    tx.commit().map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::OptionalExtension;
+
+    #[test]
+    fn runs_every_migration_up_to_current_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+
+        let on_disk_version: u32 = conn
+            .query_row("SELECT MAX(version) FROM schema_migrations", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(on_disk_version, CURRENT_DB_VERSION);
+
+        // V2 and V4 in particular ought to have left their tables behind.
+        for table in ["kv_store", "accounts", "transactions", "categories", "app_state_history"] {
+            let exists: bool = conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    [table],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()
+                .unwrap()
+                .is_some();
+            assert!(exists, "expected table {} to exist after migrating", table);
+        }
+    }
+
+    #[test]
+    fn is_idempotent_across_repeated_runs() {
+        let conn = Connection::open_in_memory().unwrap();
+        run_migrations(&conn).unwrap();
+        run_migrations(&conn).unwrap();
+
+        let applied_count: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_migrations", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(applied_count, MIGRATIONS.len() as u32);
+    }
+
+    #[test]
+    fn rejects_a_database_newer_than_this_binary_supports() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE schema_migrations (
+                version INTEGER PRIMARY KEY,
+                applied_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+            )",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO schema_migrations (version) VALUES (?1)",
+            rusqlite::params![CURRENT_DB_VERSION + 1],
+        )
+        .unwrap();
+
+        let result = run_migrations(&conn);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,162 @@
+use rusqlite::{params, Row, ToSql};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::AppDatabase;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: i64,
+    pub account_id: i64,
+    pub category_id: Option<i64>,
+    pub amount: f64,
+    pub occurred_at: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransactionInput {
+    pub account_id: i64,
+    pub category_id: Option<i64>,
+    pub amount: f64,
+    pub occurred_at: String,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TransactionFilter {
+    pub account_id: Option<i64>,
+    pub category_id: Option<i64>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategorySum {
+    pub category_id: Option<i64>,
+    pub total: f64,
+}
+
+fn row_to_transaction(row: &Row) -> rusqlite::Result<Transaction> {
+    Ok(Transaction {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        category_id: row.get(2)?,
+        amount: row.get(3)?,
+        occurred_at: row.get(4)?,
+        note: row.get(5)?,
+    })
+}
+
+#[tauri::command]
+pub fn create_transaction(
+    input: TransactionInput,
+    database: State<AppDatabase>,
+) -> Result<i64, String> {
+    database.with_conn(|conn| {
+        conn.execute(
+            "INSERT INTO transactions (account_id, category_id, amount, occurred_at, note)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                input.account_id,
+                input.category_id,
+                input.amount,
+                input.occurred_at,
+                input.note
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    })
+}
+
+#[tauri::command]
+pub fn list_transactions(
+    filter: TransactionFilter,
+    database: State<AppDatabase>,
+) -> Result<Vec<Transaction>, String> {
+    database.with_conn(|conn| {
+        let mut sql = "SELECT id, account_id, category_id, amount, occurred_at, note
+                        FROM transactions WHERE 1=1"
+            .to_string();
+        let mut bound: Vec<&dyn ToSql> = Vec::new();
+        if let Some(account_id) = &filter.account_id {
+            sql.push_str(" AND account_id = ?");
+            bound.push(account_id);
+        }
+        if let Some(category_id) = &filter.category_id {
+            sql.push_str(" AND category_id = ?");
+            bound.push(category_id);
+        }
+        if let Some(from) = &filter.from {
+            sql.push_str(" AND occurred_at >= ?");
+            bound.push(from);
+        }
+        if let Some(to) = &filter.to {
+            sql.push_str(" AND occurred_at <= ?");
+            bound.push(to);
+        }
+        sql.push_str(" ORDER BY occurred_at DESC");
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(bound.as_slice(), row_to_transaction)?;
+        rows.collect()
+    })
+}
+
+#[tauri::command]
+pub fn update_transaction(
+    id: i64,
+    input: TransactionInput,
+    database: State<AppDatabase>,
+) -> Result<(), String> {
+    let rows_affected = database.with_conn(|conn| {
+        conn.execute(
+            "UPDATE transactions
+             SET account_id = ?1, category_id = ?2, amount = ?3, occurred_at = ?4, note = ?5
+             WHERE id = ?6",
+            params![
+                input.account_id,
+                input.category_id,
+                input.amount,
+                input.occurred_at,
+                input.note,
+                id
+            ],
+        )
+    })?;
+    if rows_affected == 0 {
+        return Err(format!("{} numaralı işlem bulunamadı", id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_transaction(id: i64, database: State<AppDatabase>) -> Result<(), String> {
+    let rows_affected = database
+        .with_conn(|conn| conn.execute("DELETE FROM transactions WHERE id = ?1", params![id]))?;
+    if rows_affected == 0 {
+        return Err(format!("{} numaralı işlem bulunamadı", id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn sum_by_category(
+    month: String,
+    database: State<AppDatabase>,
+) -> Result<Vec<CategorySum>, String> {
+    database.with_conn(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT category_id, SUM(amount) FROM transactions
+             WHERE strftime('%Y-%m', occurred_at) = ?1
+             GROUP BY category_id",
+        )?;
+        let rows = stmt.query_map(params![month], |row| {
+            Ok(CategorySum {
+                category_id: row.get(0)?,
+                total: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    })
+}